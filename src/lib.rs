@@ -0,0 +1,835 @@
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, BufWriter},
+};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever [`PersistedIndex`]'s shape changes, so an index saved by an older version is
+/// rejected instead of misread.
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// We only consider subsequences that are at most this distance away from the input word
+/// and at most this distance away from a correct spelling.
+pub const DEFAULT_MAX_EDIT_DISTANCE: usize = 2;
+
+/// Dictionary words are truncated to this many characters before generating deletion
+/// subsequences, so indexing cost no longer grows with word length. Must be at least
+/// `max_edit_distance`, since that's the most characters a lookup will ever delete.
+pub const DEFAULT_PREFIX_LENGTH: usize = 7;
+
+/// The longest substring [`SymSpell::word_segment`] will consider as a single word.
+const MAX_SEGMENT_LENGTH: usize = 20;
+
+/// How thoroughly [`SymSpell::lookup`] should search for and report corrections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only the single best suggestion: minimum distance, then highest frequency.
+    Top,
+    /// Every word at the minimum distance found.
+    Closest,
+    /// Every candidate within the index's max edit distance, grouped by distance.
+    All,
+}
+
+/// A single correction candidate and its true edit distance from the looked-up word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub word: String,
+    pub distance: usize,
+}
+
+/// The outcome of a [`SymSpell::lookup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpellResult {
+    /// The looked-up word is already in the dictionary.
+    Correct,
+    /// The looked-up word isn't in the dictionary; `suggestions` holds the candidates selected
+    /// according to the requested [`Verbosity`], ordered by ascending distance then descending
+    /// frequency.
+    Incorrect { suggestions: Vec<Suggestion> },
+}
+
+/// The result of [`SymSpell::word_segment`]: a best-effort split of run-together input into
+/// dictionary words.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segmentation {
+    /// The input with spaces inserted (and each segment corrected), e.g. "the quick brown fox".
+    pub segmented: String,
+    /// The total edit distance summed across every corrected segment.
+    pub distance: usize,
+    /// The sum of `log10(probability)` of each chosen segment; higher (closer to zero) is more
+    /// plausible.
+    pub log_probability: f64,
+}
+
+/// The result of [`SymSpell::lookup_compound`]: a best-effort correction of a whole phrase,
+/// including wrongly split or joined words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundCorrection {
+    /// The corrected phrase.
+    pub phrase: String,
+    /// The total edit distance summed across every corrected term.
+    pub distance: usize,
+}
+
+/// A symmetric-delete spelling correction engine: a dictionary indexed by the subsequences
+/// produced by deleting up to `max_edit_distance` characters from each word, so that looking up
+/// a (possibly misspelled) word only requires generating its own deletion subsequences and
+/// checking which dictionary words share one, rather than comparing against every dictionary
+/// word directly.
+pub struct SymSpell {
+    max_edit_distance: usize,
+    prefix_length: usize,
+    // maps subsequences of dictionary word prefixes to (a map of distances to the full correct
+    // spellings in the dictionary)
+    dictionary_subsequences: HashMap<String, HashMap<usize, Vec<String>>>,
+    words: HashSet<String>,
+    word_counts: HashMap<String, u64>,
+    total_word_count: u64,
+    // hash of the dictionary word list this index was built from, used to detect a stale saved
+    // index whose source word list has since changed
+    source_hash: u64,
+}
+
+/// The on-disk shape written by [`SymSpell::save`] and read by [`SymSpell::load`].
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex {
+    format_version: u32,
+    max_edit_distance: usize,
+    prefix_length: usize,
+    source_hash: u64,
+    dictionary_subsequences: HashMap<String, HashMap<usize, Vec<String>>>,
+    words: HashSet<String>,
+    word_counts: HashMap<String, u64>,
+    total_word_count: u64,
+}
+
+impl SymSpell {
+    /// Builds a `SymSpell` by reading one word per line from `path`: trimming whitespace,
+    /// lowercasing, and skipping empty lines. Uses [`DEFAULT_PREFIX_LENGTH`]; see
+    /// [`Self::from_words_file_with_prefix_length`] to customize it.
+    pub fn from_words_file(path: &str, max_edit_distance: usize) -> Result<Self> {
+        Self::from_words_file_with_prefix_length(path, max_edit_distance, DEFAULT_PREFIX_LENGTH)
+    }
+
+    /// Like [`Self::from_words_file`], but with an explicit `prefix_length` (must be at least
+    /// `max_edit_distance`).
+    pub fn from_words_file_with_prefix_length(
+        path: &str,
+        max_edit_distance: usize,
+        prefix_length: usize,
+    ) -> Result<Self> {
+        let words = read_words_file(path)?;
+
+        Ok(Self::from_iter_with_prefix_length(
+            words,
+            max_edit_distance,
+            prefix_length,
+        ))
+    }
+
+    /// Builds a `SymSpell` from an in-memory list of dictionary words, using
+    /// [`DEFAULT_PREFIX_LENGTH`]; see [`Self::from_iter_with_prefix_length`] to customize it.
+    ///
+    /// We want to keep all subsequences and not just the closest ones because otherwise we
+    /// might miss valid corrections.
+    /// eg, consider input "tubr", dictionary has "tube" and "tub"
+    /// tub -> tube = 1
+    /// tub -> tub = 0 (tub is already a valid word)
+    /// if we only kept the subsequences closest to correct words then we would only keep tub
+    /// and miss tube as a correction for tubr
+    pub fn from_iter<I: IntoIterator<Item = String>>(words: I, max_edit_distance: usize) -> Self {
+        Self::from_iter_with_prefix_length(words, max_edit_distance, DEFAULT_PREFIX_LENGTH)
+    }
+
+    /// Like [`Self::from_iter`], but with an explicit `prefix_length` (must be at least
+    /// `max_edit_distance`).
+    ///
+    /// Dictionary words are truncated to their first `prefix_length` characters before
+    /// generating deletion subsequences (the full word is still stored as the value), following
+    /// the SymSpell prefix-length optimization: since the number of deletes from a fixed-length
+    /// prefix is bounded regardless of word length, this keeps indexing time and memory from
+    /// scaling with word length. Lookups truncate the same way; see [`Self::lookup`] for why
+    /// that never produces wrong answers.
+    pub fn from_iter_with_prefix_length<I: IntoIterator<Item = String>>(
+        words: I,
+        max_edit_distance: usize,
+        prefix_length: usize,
+    ) -> Self {
+        assert!(
+            prefix_length >= max_edit_distance,
+            "prefix_length must be at least max_edit_distance"
+        );
+
+        let mut dictionary_subsequences: HashMap<String, HashMap<usize, Vec<String>>> =
+            HashMap::new();
+        let mut dictionary_words = HashSet::new();
+
+        for word in words {
+            dictionary_words.insert(word.clone());
+
+            let prefix = prefix(&word, prefix_length);
+            for distance in 0..=max_edit_distance {
+                // creating subsequences from this prefix at this distance will yield empty
+                // strings
+                if prefix.len() as i32 - distance as i32 <= 0 {
+                    continue;
+                }
+
+                for subsequence in subsequences_from_n_deletions(&prefix, distance) {
+                    dictionary_subsequences
+                        .entry(subsequence)
+                        .or_insert_with(|| HashMap::with_capacity(1))
+                        .entry(distance)
+                        .or_insert_with(|| Vec::with_capacity(1))
+                        .push(word.clone());
+                }
+            }
+        }
+
+        let source_hash = hash_words(&dictionary_words);
+
+        Self {
+            max_edit_distance,
+            prefix_length,
+            dictionary_subsequences,
+            words: dictionary_words,
+            word_counts: HashMap::new(),
+            total_word_count: 0,
+            source_hash,
+        }
+    }
+
+    /// Loads a file of `word count` pairs, one per line, to rank equidistant suggestions by how
+    /// often they occur in a reference corpus. Lines that don't parse as `<word> <count>` are
+    /// skipped.
+    pub fn with_word_counts_file(mut self, path: &str) -> Result<Self> {
+        let file = File::open(path).context("could not open word counts file")?;
+        let reader = BufReader::new(file);
+
+        let mut word_counts = HashMap::new();
+        for line in reader.lines() {
+            let line = line.context("could not read from file")?;
+            let Some((word, count)) = line.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+            let Ok(count) = count.trim().parse::<u64>() else {
+                continue;
+            };
+            word_counts.insert(word.trim().to_lowercase(), count);
+        }
+
+        self.total_word_count = word_counts.values().sum();
+        self.word_counts = word_counts;
+        Ok(self)
+    }
+
+    /// Computes the same word-list hash [`Self::source_hash`] would report for a `SymSpell`
+    /// built from this words file, without actually building the (expensive) delete index.
+    /// Compare this against a saved index's [`Self::source_hash`] to decide whether
+    /// [`Self::load`] would return a fresh index or a stale one.
+    pub fn hash_words_file(path: &str) -> Result<u64> {
+        let words: HashSet<String> = read_words_file(path)?.into_iter().collect();
+        Ok(hash_words(&words))
+    }
+
+    /// A hash of the dictionary word list this index was built from. Two `SymSpell`s built from
+    /// the same words (in any order) have the same `source_hash`.
+    pub fn source_hash(&self) -> u64 {
+        self.source_hash
+    }
+
+    /// Serializes this index to `path` in a compact binary format, so it can be rebuilt near
+    /// instantly with [`Self::load`] instead of reprocessing the dictionary from scratch.
+    pub fn save(&self, path: &str) -> Result<()> {
+        let persisted = PersistedIndex {
+            format_version: INDEX_FORMAT_VERSION,
+            max_edit_distance: self.max_edit_distance,
+            prefix_length: self.prefix_length,
+            source_hash: self.source_hash,
+            dictionary_subsequences: self.dictionary_subsequences.clone(),
+            words: self.words.clone(),
+            word_counts: self.word_counts.clone(),
+            total_word_count: self.total_word_count,
+        };
+
+        let file = File::create(path).context("could not create saved index file")?;
+        bincode::serialize_into(BufWriter::new(file), &persisted)
+            .context("could not write saved index")?;
+        Ok(())
+    }
+
+    /// Loads an index previously written by [`Self::save`]. Rejects the saved index (returning
+    /// an error) if it was built with a different `max_edit_distance` or `prefix_length` than
+    /// requested here, since its delete index wouldn't match either parameter; callers that
+    /// want to also detect a changed source word list should compare [`Self::source_hash`]
+    /// (or [`Self::hash_words_file`]) themselves and fall back to rebuilding.
+    pub fn load(path: &str, max_edit_distance: usize, prefix_length: usize) -> Result<Self> {
+        let file = File::open(path).context("could not open saved index file")?;
+        let persisted: PersistedIndex = bincode::deserialize_from(BufReader::new(file))
+            .context("could not read saved index")?;
+
+        if persisted.format_version != INDEX_FORMAT_VERSION {
+            bail!(
+                "saved index has format version {}, expected {INDEX_FORMAT_VERSION}",
+                persisted.format_version
+            );
+        }
+        if persisted.max_edit_distance != max_edit_distance || persisted.prefix_length != prefix_length {
+            bail!(
+                "saved index was built with max_edit_distance={}, prefix_length={}, but {max_edit_distance}/{prefix_length} were requested",
+                persisted.max_edit_distance,
+                persisted.prefix_length,
+            );
+        }
+
+        Ok(Self {
+            max_edit_distance,
+            prefix_length,
+            dictionary_subsequences: persisted.dictionary_subsequences,
+            words: persisted.words,
+            word_counts: persisted.word_counts,
+            total_word_count: persisted.total_word_count,
+            source_hash: persisted.source_hash,
+        })
+    }
+
+    /// Returns whether `word` is present in the dictionary exactly as given.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    /// Looks up `word`, returning [`SpellResult::Correct`] if it's already a dictionary word, or
+    /// [`SpellResult::Incorrect`] with suggestions chosen according to `verbosity`.
+    ///
+    /// Candidate generation only looks at the prefix of `word` (matching how the dictionary was
+    /// indexed), but every candidate is verified against the true restricted
+    /// Damerau-Levenshtein distance over the *full* strings before being returned, so truncation
+    /// never produces wrong answers -- it only limits how far lookups search.
+    pub fn lookup(&self, word: &str, verbosity: Verbosity) -> SpellResult {
+        if self.contains(word) {
+            return SpellResult::Correct;
+        }
+
+        let input_prefix = prefix(word, self.prefix_length);
+
+        // maps (distances from input word to possible correct spellings) to correct spellings
+        let mut results: HashMap<usize, HashSet<&String>> = HashMap::new();
+        for dist_input_to_subseq in 0..=self.max_edit_distance {
+            // creating subsequences from this prefix at this distance will yield empty strings
+            if input_prefix.len() as i32 - dist_input_to_subseq as i32 <= 0 {
+                continue;
+            }
+
+            // find subsequences of input, and update results with dictionary words that share a
+            // subsequence with the input subsequence
+            for input_subsequence in
+                subsequences_from_n_deletions(&input_prefix, dist_input_to_subseq)
+            {
+                self.check_for_correct_spellings(&mut results, &input_subsequence, dist_input_to_subseq);
+            }
+        }
+
+        // re-bucket candidates by their true restricted Damerau-Levenshtein distance to the raw
+        // input word, since the distance used to collect them is only a proxy (two words
+        // sharing a deletion subsequence need not actually be that close: "abc" and "cab" share
+        // "ab" at a proxy distance of 1 but are 2 edits apart, and prefix truncation ignores
+        // everything past the prefix entirely)
+        let mut verified_results: HashMap<usize, HashSet<&String>> = HashMap::new();
+        for candidates in results.values() {
+            for candidate in candidates {
+                if let Some(true_distance) =
+                    restricted_edit_distance(word, candidate, self.max_edit_distance)
+                {
+                    verified_results
+                        .entry(true_distance)
+                        .or_insert_with(|| HashSet::with_capacity(1))
+                        .insert(*candidate);
+                }
+            }
+        }
+
+        SpellResult::Incorrect {
+            suggestions: self.select_suggestions(verbosity, &verified_results),
+        }
+    }
+
+    /// Updates `results` from (distance from input word to possible correct spellings) to
+    /// correct spellings by checking if `input_subsequence` (a subsequence of the input word) is
+    /// also a subsequence of any dictionary words. If it is, those dictionary words are stored
+    /// in `results` with the key being max(`dist_input_to_subseq`: distance from the input word
+    /// to `input_subsequence`, distance from `input_subsequence` to correct spellings)
+    fn check_for_correct_spellings<'a>(
+        &'a self,
+        results: &mut HashMap<usize, HashSet<&'a String>>,
+        input_subsequence: &String,
+        dist_input_to_subseq: usize,
+    ) {
+        if let Some(subseq_dist_to_correct_spelling) =
+            self.dictionary_subsequences.get(input_subsequence)
+        {
+            for (dist_subseq_to_correction, correct_spellings) in subseq_dist_to_correct_spelling {
+                results
+                    // we use the max of distance from input to subsequence and distance from
+                    // subsequence to correct spelling so that we don't favor the subsequence
+                    // when it is itself a correct spelling
+                    // eg, consider input "tubr", dictionary has "tube" and "tub"
+                    // tubr -> tub = 1
+                    // tub -> tube = 1
+                    // since we're using the max, tubr is 1 away from both tube and tub, but if
+                    // we were using a sum of distances, for example, tub would be 1 away while
+                    // tube would be 1 + 1 = 2 away
+                    .entry(dist_input_to_subseq.max(*dist_subseq_to_correction))
+                    .or_insert_with(|| HashSet::with_capacity(1))
+                    .extend(correct_spellings);
+            }
+        }
+    }
+
+    /// Selects and orders suggestions out of `verified_results` (candidates bucketed by true
+    /// edit distance) according to `verbosity`.
+    fn select_suggestions(
+        &self,
+        verbosity: Verbosity,
+        verified_results: &HashMap<usize, HashSet<&String>>,
+    ) -> Vec<Suggestion> {
+        let Some(min_distance) = verified_results.keys().min().copied() else {
+            return Vec::new();
+        };
+
+        let distances = match verbosity {
+            Verbosity::Top | Verbosity::Closest => vec![min_distance],
+            Verbosity::All => (min_distance..=self.max_edit_distance).collect(),
+        };
+
+        let mut suggestions = Vec::new();
+        for distance in distances {
+            let Some(candidates) = verified_results.get(&distance) else {
+                continue;
+            };
+            let mut candidates: Vec<&String> = candidates.iter().copied().collect();
+            self.sort_by_frequency(&mut candidates);
+            suggestions.extend(candidates.into_iter().map(|word| Suggestion {
+                word: word.clone(),
+                distance,
+            }));
+
+            if verbosity == Verbosity::Top {
+                suggestions.truncate(1);
+                break;
+            }
+        }
+
+        suggestions
+    }
+
+    /// Sorts candidates by descending frequency, falling back to length then alphabetically for
+    /// words that don't appear in the word counts.
+    fn sort_by_frequency(&self, candidates: &mut [&String]) {
+        candidates.sort_by(|a, b| {
+            match self
+                .word_counts
+                .get(*b)
+                .unwrap_or(&0)
+                .cmp(self.word_counts.get(*a).unwrap_or(&0))
+            {
+                Ordering::Equal => match a.len().cmp(&b.len()) {
+                    Ordering::Equal => a.cmp(b),
+                    x => x,
+                },
+                x => x,
+            }
+        });
+    }
+
+    /// Inserts spaces into `input`, a string that may have lost them (e.g. "thequickbrownfox"
+    /// -> "the quick brown fox"), by trying every split into dictionary words and keeping the
+    /// one with the highest total log-probability.
+    ///
+    /// This is a DP over character positions: for each end position, every substring ending
+    /// there (up to [`MAX_SEGMENT_LENGTH`] characters) is corrected via [`Self::lookup`] and
+    /// scored by summing `log10(count / total_word_count)` of the chosen words (discounted by
+    /// their edit distance, so a more frequent word several edits away doesn't beat an exact or
+    /// near match), with unknown words given a small smoothed probability that shrinks tenfold
+    /// with each character. The best cumulative score and a back-pointer are kept per end position and walked
+    /// backwards to reconstruct the split.
+    pub fn word_segment(&self, input: &str) -> Segmentation {
+        let chars: Vec<char> = input.trim().to_lowercase().chars().collect();
+        let n = chars.len();
+        let total_word_count = (self.total_word_count.max(1)) as f64;
+
+        // best_score[i]/best_log_probability[i]/best_distance[i]/best_word[i] describe the best
+        // segmentation of chars[..i], with best_back[i] pointing at where its last word started
+        let mut best_score = vec![f64::NEG_INFINITY; n + 1];
+        let mut best_log_probability = vec![0.0_f64; n + 1];
+        let mut best_distance = vec![0_usize; n + 1];
+        let mut best_back = vec![0_usize; n + 1];
+        let mut best_word = vec![String::new(); n + 1];
+        best_score[0] = 0.0;
+
+        for end in 1..=n {
+            for start in end.saturating_sub(MAX_SEGMENT_LENGTH)..end {
+                if best_score[start] == f64::NEG_INFINITY {
+                    continue;
+                }
+
+                let segment: String = chars[start..end].iter().collect();
+                let (word, distance, probability) = self.correct_segment(&segment, total_word_count);
+                // an edit costs roughly an order of magnitude of plausibility, same scale as
+                // the per-character smoothing below, so a correction is only preferred over a
+                // more frequent but more heavily edited one when it more than makes up for it
+                let score = best_score[start] + probability.log10() - distance as f64;
+                let candidate_distance = best_distance[start] + distance;
+
+                // different split points can land on the same sequence of corrected words and
+                // so tie on score exactly; prefer the split with the lower total edit distance
+                let better = score > best_score[end]
+                    || (score == best_score[end] && candidate_distance < best_distance[end]);
+                if better {
+                    best_score[end] = score;
+                    best_log_probability[end] = best_log_probability[start] + probability.log10();
+                    best_distance[end] = candidate_distance;
+                    best_back[end] = start;
+                    best_word[end] = word;
+                }
+            }
+        }
+
+        let mut words = Vec::new();
+        let mut position = n;
+        while position > 0 {
+            words.push(std::mem::take(&mut best_word[position]));
+            position = best_back[position];
+        }
+        words.reverse();
+
+        Segmentation {
+            segmented: words.join(" "),
+            distance: best_distance[n],
+            log_probability: best_log_probability[n],
+        }
+    }
+
+    /// Corrects a single candidate segment for [`Self::word_segment`], returning the chosen
+    /// word, its edit distance from `segment`, and its estimated probability.
+    fn correct_segment(&self, segment: &str, total_word_count: f64) -> (String, usize, f64) {
+        let (word, distance) = self.best_correction(segment);
+
+        let probability = match self.word_counts.get(&word) {
+            Some(&count) => count as f64 / total_word_count,
+            None => smoothed_probability(word.len(), total_word_count),
+        };
+
+        (word, distance, probability)
+    }
+
+    /// Returns the single best correction for `word` (itself, if already a dictionary word;
+    /// otherwise the top suggestion; otherwise `word` unchanged with its length as the
+    /// distance, since it couldn't be corrected at all).
+    fn best_correction(&self, word: &str) -> (String, usize) {
+        match self.lookup(word, Verbosity::Top) {
+            SpellResult::Correct => (word.to_owned(), 0),
+            SpellResult::Incorrect { mut suggestions } if !suggestions.is_empty() => {
+                let top = suggestions.remove(0);
+                (top.word, top.distance)
+            }
+            SpellResult::Incorrect { .. } => (word.to_owned(), word.len()),
+        }
+    }
+
+    /// Corrects a whole phrase, handling terms that should be merged with their neighbor or
+    /// split into two dictionary words, not just individually misspelled.
+    ///
+    /// Walks the whitespace-separated terms left to right. For each term, compares three
+    /// options: correcting it alone via [`Self::lookup`], merging it with the next term and
+    /// correcting the concatenation (handling wrongly split words like "in put" -> "input"),
+    /// and splitting it into two dictionary words (handling wrongly joined words like
+    /// "whereis" -> "where is"). Whichever option has the lowest edit distance wins; frequency
+    /// is never compared across options, since merging/splitting changes how many words are
+    /// produced and a single word's frequency isn't commensurable with two words' combined
+    /// frequency (it's only used within [`Self::best_split`] to pick among splits, and within
+    /// [`Self::best_correction`] to pick among same-distance single-word candidates).
+    pub fn lookup_compound(&self, phrase: &str) -> CompoundCorrection {
+        let terms: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+
+        let mut corrected_words = Vec::new();
+        let mut total_distance = 0;
+
+        let mut i = 0;
+        while i < terms.len() {
+            let (single_word, single_distance) = self.best_correction(&terms[i]);
+            let mut best_words = vec![single_word];
+            let mut best_distance = single_distance;
+            let mut best_consumed = 1;
+
+            if i + 1 < terms.len() {
+                let merged = format!("{}{}", terms[i], terms[i + 1]);
+                let (merged_word, merged_distance) = self.best_correction(&merged);
+
+                // compare against correcting both terms separately, not just term i alone --
+                // otherwise a merge only ever wins when term i+1's cost is ignored
+                let (_, next_distance) = self.best_correction(&terms[i + 1]);
+                let separate_distance = best_distance + next_distance;
+
+                if merged_distance < separate_distance {
+                    best_words = vec![merged_word];
+                    best_distance = merged_distance;
+                    best_consumed = 2;
+                }
+            }
+
+            // a split is only worth considering when neither term i's direct correction nor a
+            // merge is exact -- a term that's already a dictionary word should never be
+            // shredded into two shorter dictionary words just because they
+            // happen to exist (e.g. "atone" into "a" + "tone")
+            if single_distance > 0
+                && best_distance > 0
+                && let Some((first_half, second_half)) = self.best_split(&terms[i])
+            {
+                best_words = vec![first_half, second_half];
+                best_distance = 0;
+                best_consumed = 1;
+            }
+
+            corrected_words.extend(best_words);
+            total_distance += best_distance;
+            i += best_consumed;
+        }
+
+        CompoundCorrection {
+            phrase: corrected_words.join(" "),
+            distance: total_distance,
+        }
+    }
+
+    /// Finds the split of `term` into two dictionary words with the highest combined frequency,
+    /// if any such split exists.
+    fn best_split(&self, term: &str) -> Option<(String, String)> {
+        let chars: Vec<char> = term.chars().collect();
+
+        (1..chars.len())
+            .filter_map(|i| {
+                let first_half: String = chars[..i].iter().collect();
+                let second_half: String = chars[i..].iter().collect();
+                (self.contains(&first_half) && self.contains(&second_half))
+                    .then_some((first_half, second_half))
+            })
+            .max_by_key(|(first_half, second_half)| {
+                self.word_frequency(first_half) + self.word_frequency(second_half)
+            })
+    }
+
+    /// Returns how often `word` occurs in the reference corpus, or 0 if it's unknown.
+    fn word_frequency(&self, word: &str) -> u64 {
+        self.word_counts.get(word).copied().unwrap_or(0)
+    }
+}
+
+/// A small smoothed probability for a word of length `len` that has no observed frequency,
+/// shrinking tenfold with each extra character so that shorter unknown words are treated as
+/// more plausible than longer ones.
+fn smoothed_probability(len: usize, total_word_count: f64) -> f64 {
+    10.0 / (total_word_count * 10_f64.powi(len as i32))
+}
+
+/// Computes the restricted (optimal string alignment) Damerau-Levenshtein distance between `a`
+/// and `b`: the minimum number of insertions, deletions, substitutions, and transpositions of
+/// adjacent characters needed to turn `a` into `b`, where each substring may be edited at most
+/// once. Returns `None` if the distance exceeds `max_edit_distance`, either because the length
+/// difference alone already rules it out (checked early, before doing any DP work) or because
+/// the computed distance does.
+fn restricted_edit_distance(a: &str, b: &str, max_edit_distance: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+
+    if (n as i64 - m as i64).unsigned_abs() as usize > max_edit_distance {
+        return None;
+    }
+
+    // dcol holds the row currently being computed, row_before the row before it, and prev_dcol
+    // the row before that one (needed to look up the distance two rows and two columns back for
+    // transpositions)
+    let mut prev_dcol: Vec<usize> = vec![0; m + 1];
+    let mut dcol: Vec<usize> = (0..=m).collect();
+
+    for i in 0..n {
+        let row_before = dcol.clone();
+        let mut current = i;
+        dcol[0] = i + 1;
+
+        for j in 0..m {
+            let next = row_before[j + 1];
+            if a[i] == b[j] {
+                dcol[j + 1] = current;
+            } else {
+                dcol[j + 1] = current.min(next).min(dcol[j]) + 1;
+                if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                    dcol[j + 1] = dcol[j + 1].min(prev_dcol[j - 1] + 1);
+                }
+            }
+            current = next;
+        }
+
+        prev_dcol = row_before;
+    }
+
+    (dcol[m] <= max_edit_distance).then_some(dcol[m])
+}
+
+/// Reads one word per line from `path`: trimming whitespace, lowercasing, and skipping empty
+/// lines.
+fn read_words_file(path: &str) -> Result<Vec<String>> {
+    let words_file = File::open(path).context("could not open words file")?;
+    let words_reader = BufReader::new(words_file);
+
+    words_reader
+        .lines()
+        .map(|line_result| line_result.map(|line| line.trim().to_lowercase()))
+        .filter(|line_result| line_result.as_ref().map_or(true, |line| !line.is_empty()))
+        .collect::<Result<Vec<_>, _>>()
+        .context("could not read from file")
+}
+
+/// Hashes a dictionary word list order-independently, by hashing the words in sorted order.
+fn hash_words(words: &HashSet<String>) -> u64 {
+    let mut sorted_words: Vec<&String> = words.iter().collect();
+    sorted_words.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for word in sorted_words {
+        word.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Returns the first `prefix_length` characters of `s`, or all of `s` if it's shorter.
+fn prefix(s: &str, prefix_length: usize) -> String {
+    s.chars().take(prefix_length).collect()
+}
+
+/// Returns all possible subsequences that can be created by deleting n characters from s
+fn subsequences_from_n_deletions(s: &str, n: usize) -> Vec<String> {
+    if n == 0 {
+        return vec![s.to_owned()];
+    }
+
+    let combinations = (0..s.len()).combinations(n);
+
+    let mut subsequences = Vec::new();
+    for indices in combinations {
+        let new_word = s
+            .chars()
+            .enumerate()
+            .filter(|(i, _)| !indices.contains(i))
+            .map(|(_, c)| c)
+            .collect();
+        subsequences.push(new_word);
+    }
+
+    subsequences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Builds a `SymSpell` with explicit word counts, without requiring a real corpus file on
+    /// disk: writes `counts` to a temporary file in the format [`SymSpell::with_word_counts_file`]
+    /// expects, loads it, then cleans up.
+    fn symspell_with_word_counts(words: &[&str], counts: &[(&str, u64)]) -> SymSpell {
+        let symspell = SymSpell::from_iter(words.iter().map(|w| w.to_string()), 2);
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("symspell_test_counts_{:?}.txt", std::thread::current().id()));
+        let mut file = File::create(&path).expect("could not create temp counts file");
+        for (word, count) in counts {
+            writeln!(file, "{word} {count}").expect("could not write temp counts file");
+        }
+        drop(file);
+
+        let symspell = symspell
+            .with_word_counts_file(path.to_str().unwrap())
+            .expect("could not load temp counts file");
+        std::fs::remove_file(&path).expect("could not remove temp counts file");
+        symspell
+    }
+
+    #[test]
+    fn lookup_drops_candidates_past_max_edit_distance() {
+        // "abcdefguvw" shares a 7-char prefix with the input but differs in all 3 remaining
+        // characters, a true distance of 3 -- past the max of 2 even though the proxy distance
+        // used to collect candidates during prefix-truncated matching is 0.
+        let symspell =
+            SymSpell::from_iter_with_prefix_length(["abcdefguvw".to_string()], 2, 7);
+
+        let result = symspell.lookup("abcdefgxyz", Verbosity::Closest);
+        assert_eq!(
+            result,
+            SpellResult::Incorrect {
+                suggestions: Vec::new()
+            }
+        );
+    }
+
+    #[test]
+    fn lookup_top_returns_the_true_closest_candidate() {
+        // "abcdefg" is a proxy distance-0 match (it's literally the input's prefix), but
+        // "abcdefxhi" is only a single substitution away from the full input -- closer than
+        // "abcdefg"'s true distance of 2. Top must not stop searching as soon as it sees a
+        // proxy-0 match.
+        let symspell = SymSpell::from_iter_with_prefix_length(
+            ["abcdefg".to_string(), "abcdefxhi".to_string()],
+            2,
+            7,
+        );
+
+        let result = symspell.lookup("abcdefghi", Verbosity::Top);
+        assert_eq!(
+            result,
+            SpellResult::Incorrect {
+                suggestions: vec![Suggestion {
+                    word: "abcdefxhi".to_string(),
+                    distance: 1,
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn lookup_compound_merges_wrongly_split_words() {
+        // "put" isn't in the dictionary, so correcting it alone costs distance 2 (its closest
+        // match is "input", two insertions away), while the merge "input" is an exact match
+        // (distance 0) --
+        // the merge should win even though "in" alone is more frequent than "input".
+        let symspell =
+            symspell_with_word_counts(&["in", "input"], &[("in", 1000), ("input", 500)]);
+
+        let correction = symspell.lookup_compound("in put");
+        assert_eq!(correction.phrase, "input");
+        assert_eq!(correction.distance, 0);
+    }
+
+    #[test]
+    fn lookup_compound_does_not_split_a_word_that_is_already_correct() {
+        // "atone" is itself a dictionary word, even though it also happens to be splittable
+        // into two shorter dictionary words ("a" + "tone"). It must not be shredded just
+        // because "a" is common -- a correct word's direct correction (distance 0) always
+        // beats a split, which only exists to rescue a term that doesn't correct well alone.
+        let symspell = symspell_with_word_counts(
+            &["a", "tone", "atone"],
+            &[("a", 1000), ("tone", 50), ("atone", 100)],
+        );
+
+        let correction = symspell.lookup_compound("atone");
+        assert_eq!(correction.phrase, "atone");
+        assert_eq!(correction.distance, 0);
+    }
+}